@@ -1,35 +1,144 @@
 use fastrand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
-pub enum Player {
-  Player1,
-  Player2,
-}
-
-/// The winner of a game (repeated rounds, until one player has the entire deck).
-/// The game may draw if both players flip their last card in a war.
+/// The winner of a game (repeated rounds, until one player holds every remaining
+/// card). The game may draw if a final simultaneous war eliminates every remaining
+/// player at once. Players are identified by their index into the `Vec<PlayerDeck>`
+/// the game was created with.
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum GameResult {
-  Player1,
-  Player2,
+  Winner(usize),
   Draw,
 }
 
 /// The winner of an individual round, which may consist of one or more wars.
 enum RoundResult {
   GameResult(GameResult),
-  RoundWin(Player),
+  RoundWin(usize),
+}
+
+/// A standard playing-card rank, Two through Ace.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rank {
+  Two,
+  Three,
+  Four,
+  Five,
+  Six,
+  Seven,
+  Eight,
+  Nine,
+  Ten,
+  Jack,
+  Queen,
+  King,
+  Ace,
+}
+
+const RANKS: [Rank; 13] = [
+  Rank::Two,
+  Rank::Three,
+  Rank::Four,
+  Rank::Five,
+  Rank::Six,
+  Rank::Seven,
+  Rank::Eight,
+  Rank::Nine,
+  Rank::Ten,
+  Rank::Jack,
+  Rank::Queen,
+  Rank::King,
+  Rank::Ace,
+];
+
+impl Rank {
+  /// This rank's numeric value (2-14), used for margin-based honor checks and as the
+  /// war length in `WarMode::Recursive`.
+  fn value(self) -> u8 {
+    self as u8 + 2
+  }
+}
+
+/// A playing-card suit, ordered Clubs < Diamonds < Hearts < Spades (the traditional
+/// bridge ranking) for use as a tie-break.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Suit {
+  Clubs,
+  Diamonds,
+  Hearts,
+  Spades,
+}
+
+const SUITS: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+/// A single playing card: a standard rank/suit pair, or a wildcard Joker.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Card {
+  Standard(Rank, Suit),
+  Joker,
+}
+
+impl Card {
+  /// This card's rank, for battle comparisons. `None` for a Joker, which is resolved
+  /// separately as a wildcard.
+  fn rank(self) -> Option<Rank> {
+    match self {
+      Card::Standard(rank, _) => Some(rank),
+      Card::Joker => None,
+    }
+  }
+}
+
+/// Builds a standard 52-card deck in a fixed rank/suit order (unshuffled), optionally
+/// including the two Jokers.
+pub fn deck(with_jokers: bool) -> Vec<Card> {
+  let mut cards: Vec<Card> = RANKS
+    .iter()
+    .flat_map(|&rank| SUITS.iter().map(move |&suit| Card::Standard(rank, suit)))
+    .collect();
+
+  if with_jokers {
+    cards.extend([Card::Joker, Card::Joker]);
+  }
+
+  cards
+}
+
+/// How an equal-rank battle is resolved.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum TieBreak {
+  /// Equal ranks always trigger a war, regardless of suit.
+  War,
+  /// Equal ranks are broken by suit (higher suit wins); a war only happens when both
+  /// rank and suit match, which requires Jokers or multiple decks.
+  Suit,
+}
+
+/// How a Joker behaves in a battle.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum JokerRule {
+  /// A Joker immediately wins its battle against any standard card, no war. Two Jokers
+  /// tie, which triggers a war.
+  AlwaysWins,
+  /// A Joker is wild: it's treated as matching the opposing card's rank, forcing a tie
+  /// (and therefore a war) instead of an outright win.
+  WildOnTie,
 }
 
 /// The cards owned by one player. Cards are drawn from the deck, until it is empty,
-/// at which point the entire discard is shuffled to become the new deck.
-#[derive(Clone)]
+/// at which point the entire discard becomes the new deck.
+#[derive(Clone, Hash)]
 pub struct PlayerDeck {
-  deck: Vec<u8>,
-  discard: Vec<u8>,
+  deck: Vec<Card>,
+  discard: Vec<Card>,
 }
 
 impl PlayerDeck {
-  pub fn new(deck: Vec<u8>) -> Self {
+  pub fn new(deck: Vec<Card>) -> Self {
     Self {
       deck: Vec::new(),
       discard: deck,
@@ -40,116 +149,398 @@ impl PlayerDeck {
     self.deck.len() + self.discard.len()
   }
 
-  fn draw(&mut self, rng: &mut Rng) -> Option<u8> {
+  /// Draws the top card, reshuffling the discard pile into the deck first if needed.
+  /// When `shuffle` is `false`, the discard pile is reversed onto the deck instead of
+  /// randomized, which keeps the resulting sequence of states deterministic.
+  fn draw(&mut self, rng: &mut Rng, shuffle: bool) -> Option<Card> {
     if self.deck.is_empty() {
-      rng.shuffle(&mut self.discard);
+      if shuffle {
+        rng.shuffle(&mut self.discard);
+      } else {
+        self.discard.reverse();
+      }
       std::mem::swap(&mut self.deck, &mut self.discard);
     }
 
     self.deck.pop()
   }
 
-  fn win_loot(&mut self, cards: &[u8]) {
+  fn win_loot(&mut self, cards: &[Card]) {
     self.discard.extend_from_slice(cards);
   }
+
+  /// Copies the top `n` cards off this player's deck (deck first, then discard) without
+  /// removing them, for use in a recursive war sub-game. Returns `None` if the player
+  /// does not hold `n` cards in total.
+  fn copy_top(&self, n: usize) -> Option<Vec<Card>> {
+    if self.cards() < n {
+      return None;
+    }
+
+    let mut cards: Vec<Card> = self.deck.iter().rev().take(n).copied().collect();
+    cards.extend(self.discard.iter().take(n - cards.len()).copied());
+    Some(cards)
+  }
+}
+
+/// How a tied battle is resolved.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum WarMode {
+  /// Each player flips `k` cards face-down (capped by their remaining deck, less the
+  /// card they need to keep in reserve) before comparing again.
+  FaceDown { k: usize },
+  /// Each player copies the next `card value` cards off their deck and plays those
+  /// copies out as a full recursive sub-game; the sub-game's winner takes the tie.
+  /// Mirrors Recursive Combat. Falls back to the ordinary `FaceDown` war — flipping
+  /// `fallback_k` cards and re-comparing — when a player doesn't hold enough cards to
+  /// copy, when the tied card is a Joker (which has no rank to use as a war length), or
+  /// when three or more players are tied.
+  Recursive { fallback_k: usize },
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Params {
-  /// k cards are flipped face-down in a war
-  k: usize,
+  /// The number of players in the game. At least 2.
+  players: usize,
+  /// How tied battles are resolved.
+  war_mode: WarMode,
   /// If a card loses a battle by honor_threshold or less, it is removed from the game
   honor_threshold: u8,
+  /// If set, the game is declared a `Draw` once this many rounds have been played.
+  max_turns: Option<u64>,
+  /// Whether reshuffling a discard pile into a deck is randomized. Disabled by
+  /// `with_deterministic_reshuffle` for reproducible, hashable game states.
+  shuffle_discards: bool,
+  /// How equal-rank battles are broken.
+  tie_break: TieBreak,
+  /// How Jokers behave in a battle.
+  joker_rule: JokerRule,
+  /// Whether `Game` records a `RoundRecord` per round, retrievable via `Game::history`.
+  record_history: bool,
 }
 
 impl Default for Params {
   fn default() -> Self {
-    Self::new(3, 0)
+    Self::new(2, WarMode::FaceDown { k: 3 }, 0)
   }
 }
 
 impl Params {
-  pub fn new(k: usize, honor_threshold: u8) -> Self {
-    Self { k, honor_threshold }
+  pub fn new(players: usize, war_mode: WarMode, honor_threshold: u8) -> Self {
+    Self {
+      players,
+      war_mode,
+      honor_threshold,
+      max_turns: None,
+      shuffle_discards: true,
+      tie_break: TieBreak::War,
+      joker_rule: JokerRule::AlwaysWins,
+      record_history: false,
+    }
+  }
+
+  /// The number of players this game was configured for.
+  pub fn players(&self) -> usize {
+    self.players
+  }
+
+  /// Caps the game at `max_turns` rounds, beyond which it is declared a `Draw`. War is
+  /// notorious for non-terminating games, so callers simulating many games should set
+  /// this to bound worst-case runtime.
+  pub fn with_max_turns(mut self, max_turns: u64) -> Self {
+    self.max_turns = Some(max_turns);
+    self
+  }
+
+  /// Makes discard-pile reshuffling deterministic (a reversal, rather than a random
+  /// shuffle), so that two games dealt the same starting decks always pass through the
+  /// same sequence of states. Required for the exact-state cycle detection in `play` to
+  /// be meaningful, since a randomized reshuffle makes every state effectively unique.
+  pub fn with_deterministic_reshuffle(mut self) -> Self {
+    self.shuffle_discards = false;
+    self
+  }
+
+  /// Sets how equal-rank battles are broken. Defaults to `TieBreak::War`.
+  pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+    self.tie_break = tie_break;
+    self
   }
+
+  /// Sets how Jokers behave in a battle. Defaults to `JokerRule::AlwaysWins`.
+  pub fn with_joker_rule(mut self, joker_rule: JokerRule) -> Self {
+    self.joker_rule = joker_rule;
+    self
+  }
+
+  /// Opts into recording a `RoundRecord` for every round played, retrievable afterwards
+  /// via `Game::history`, so a completed game can be inspected or replayed step-by-step.
+  pub fn with_history(mut self) -> Self {
+    self.record_history = true;
+    self
+  }
+}
+
+/// A record of a single round: the cards compared, any war that resulted, cards the
+/// honor rule removed from the game, and who collected the loot pile.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RoundRecord {
+  /// Every comparison made this round: the initial draw (one card per active player),
+  /// then one more each time the round escalated into a face-down war between whichever
+  /// players tied for the lead. Each entry pairs a player's index with their card.
+  pub comparisons: Vec<Vec<(usize, Card)>>,
+  /// The cards each tied player flipped face-down between comparisons, one entry per
+  /// escalation (so `face_down.len() == comparisons.len() - 1`).
+  pub face_down: Vec<Vec<(usize, Vec<Card>)>>,
+  /// If the last comparison tied exactly two players and was instead resolved by a
+  /// recursive sub-game (`WarMode::Recursive`), that sub-game's result. The sub-game's
+  /// own round-by-round history is not recorded here.
+  pub recursive_result: Option<GameResult>,
+  /// Cards removed from the game by the honor rule instead of joining the loot pile,
+  /// in the order they were removed (a single comparison may remove several).
+  pub honor_removals: Vec<Card>,
+  /// Who collected the loot pile this round, or `None` if the round instead ended the
+  /// game (one player held every card, a simultaneous war eliminated everyone, or a
+  /// recursive sub-game drew).
+  pub winner: Option<usize>,
 }
 
 pub struct Game {
   params: Params,
   rng: Rng,
-  player1: PlayerDeck,
-  player2: PlayerDeck,
+  players: Vec<PlayerDeck>,
 
   /// A workspace vector, storing all the cards won in a single round
-  work: Vec<u8>,
+  work: Vec<Card>,
+
+  /// The per-round history recorded so far, if `Params::with_history` was set.
+  history: Option<Vec<RoundRecord>>,
 }
 
 impl Game {
   /// Create (but do not simulate) a new game with the given player decks.
-  pub fn new(params: Params, rng: Rng, player1: PlayerDeck, player2: PlayerDeck) -> Self {
+  pub fn new(params: Params, rng: Rng, players: Vec<PlayerDeck>) -> Self {
     Self {
       params,
       rng,
-      player1,
-      player2,
+      players,
       work: Vec::new(),
+      history: params.record_history.then(Vec::new),
+    }
+  }
+
+  /// The per-round history recorded so far, if `Params::with_history` was set.
+  pub fn history(&self) -> Option<&[RoundRecord]> {
+    self.history.as_deref()
+  }
+
+  /// Each of `contenders` flips up to `k` face-down cards (leaving at least one card in
+  /// their deck) into the loot pile, ahead of the next comparison. Returns the cards
+  /// each contender flipped.
+  fn flip_face_down(&mut self, contenders: &[usize], k: usize) -> Vec<(usize, Vec<Card>)> {
+    let shuffle = self.params.shuffle_discards;
+
+    contenders
+      .iter()
+      .map(|&i| {
+        let n = self.players[i].cards().saturating_sub(1).min(k);
+        let flips: Vec<Card> = (0..n)
+          .map(|_| self.players[i].draw(&mut self.rng, shuffle).unwrap())
+          .collect();
+        self.work.extend(flips.iter().copied());
+        (i, flips)
+      })
+      .collect()
+  }
+
+  /// Compares two drawn cards, accounting for `tie_break` and `joker_rule`.
+  fn compare(&self, card1: Card, card2: Card) -> Ordering {
+    match (card1, card2) {
+      (Card::Joker, Card::Joker) => Ordering::Equal,
+      (Card::Joker, _) => match self.params.joker_rule {
+        JokerRule::AlwaysWins => Ordering::Greater,
+        JokerRule::WildOnTie => Ordering::Equal,
+      },
+      (_, Card::Joker) => match self.params.joker_rule {
+        JokerRule::AlwaysWins => Ordering::Less,
+        JokerRule::WildOnTie => Ordering::Equal,
+      },
+      (Card::Standard(rank1, suit1), Card::Standard(rank2, suit2)) => {
+        rank1.cmp(&rank2).then_with(|| match self.params.tie_break {
+          TieBreak::War => Ordering::Equal,
+          TieBreak::Suit => suit1.cmp(&suit2),
+        })
+      }
     }
   }
 
-  fn play_round(&mut self) -> RoundResult {
-    let Params { k, honor_threshold } = self.params;
+  fn play_round(&mut self) -> (RoundResult, RoundRecord) {
+    let Params {
+      war_mode,
+      honor_threshold,
+      shuffle_discards,
+      ..
+    } = self.params;
     self.work.clear();
 
+    let mut record = RoundRecord {
+      comparisons: Vec::new(),
+      face_down: Vec::new(),
+      recursive_result: None,
+      honor_removals: Vec::new(),
+      winner: None,
+    };
+
+    // Players still holding cards at the start of the round. The game is over once at
+    // most one remains.
+    let mut contenders: Vec<usize> = (0..self.players.len())
+      .filter(|&i| self.players[i].cards() > 0)
+      .collect();
+
+    if contenders.len() <= 1 {
+      let result = match contenders.first() {
+        Some(&winner) => GameResult::Winner(winner),
+        None => GameResult::Draw,
+      };
+      return (RoundResult::GameResult(result), record);
+    }
+
     loop {
-      // Each player plays a card, if possible. If they are out of cards, they have lost
-      let (card1, card2) = match (
-        self.player1.draw(&mut self.rng),
-        self.player2.draw(&mut self.rng),
-      ) {
-        (None, None) => return RoundResult::GameResult(GameResult::Draw),
-        (None, Some(_)) => return RoundResult::GameResult(GameResult::Player2),
-        (Some(_), None) => return RoundResult::GameResult(GameResult::Player1),
-        (Some(card1), Some(card2)) => (card1, card2),
+      // Each contender plays a card. A contender who can't (having been whittled down
+      // to nothing during a war escalation) simply drops out.
+      let draws: Vec<(usize, Card)> = contenders
+        .iter()
+        .filter_map(|&i| self.players[i].draw(&mut self.rng, shuffle_discards).map(|card| (i, card)))
+        .collect();
+
+      // A simultaneous elimination: nobody is left standing to claim the loot pile.
+      let Some(&(_, best)) = draws.iter().max_by(|(_, a), (_, b)| self.compare(*a, *b)) else {
+        return (RoundResult::GameResult(GameResult::Draw), record);
       };
+      record.comparisons.push(draws.clone());
 
-      // Honorable war: if the losing card lost by a small enough margin, remove it from the game.
-      // Otherwise, append both cards to the win pile.
-      if card1 != card2 && card1.abs_diff(card2) <= honor_threshold {
-        self.work.extend([card1.max(card2)]);
-      } else {
-        self.work.extend([card1, card2]);
+      // Honorable war: any card that lost this comparison by a small enough rank margin
+      // is removed from the game instead of going to the loot pile. Jokers have no rank
+      // and are exempt.
+      for &(_, card) in &draws {
+        let honor_removed = matches!(
+          (card.rank(), best.rank()),
+          (Some(rank), Some(best_rank))
+            if self.compare(card, best) != Ordering::Equal
+              && rank.value().abs_diff(best_rank.value()) <= honor_threshold
+        );
+
+        if honor_removed {
+          record.honor_removals.push(card);
+        } else {
+          self.work.push(card);
+        }
+      }
+
+      // Whoever is still tied for the lead goes to war; everyone else sits this war out.
+      let tied: Vec<usize> = draws
+        .iter()
+        .filter(|&&(_, card)| self.compare(card, best) == Ordering::Equal)
+        .map(|&(i, _)| i)
+        .collect();
+
+      if tied.len() == 1 {
+        let winner = tied[0];
+        record.winner = Some(winner);
+        return (RoundResult::RoundWin(winner), record);
       }
 
-      // If the cards are different, one player wins the round
-      // If the cards are equal, each player plays up to `k` face-down cards (leaving at least one card in their deck) and we repeat
-      match card1.cmp(&card2) {
-        Ordering::Greater => return RoundResult::RoundWin(Player::Player1),
-        Ordering::Less => return RoundResult::RoundWin(Player::Player2),
-
-        Ordering::Equal => {
-          let n = self.player1.cards().saturating_sub(1).min(k);
-          self
-            .work
-            .extend((0..n).map(|_| self.player1.draw(&mut self.rng).unwrap()));
-
-          let n = self.player2.cards().saturating_sub(1).min(k);
-          self
-            .work
-            .extend((0..n).map(|_| self.player2.draw(&mut self.rng).unwrap()));
+      // A tied player who has just played their last card drops out of the war rather
+      // than continuing to contend for the loot pile.
+      contenders = tied.into_iter().filter(|&i| self.players[i].cards() > 0).collect();
+
+      match contenders.as_slice() {
+        [] => return (RoundResult::GameResult(GameResult::Draw), record),
+        &[winner] => {
+          record.winner = Some(winner);
+          return (RoundResult::RoundWin(winner), record);
+        }
+        _ => {}
+      }
+
+      match war_mode {
+        WarMode::FaceDown { k } => record.face_down.push(self.flip_face_down(&contenders, k)),
+
+        // Recursive sub-games mirror the two-player rule from Recursive Combat; with
+        // three or more players still tied, fall back to an ordinary face-down war.
+        WarMode::Recursive { fallback_k } if contenders.len() == 2 && best.rank().is_some() => {
+          let c = best.rank().unwrap().value() as usize;
+          let (player1, player2) = (contenders[0], contenders[1]);
+
+          match (self.players[player1].copy_top(c), self.players[player2].copy_top(c)) {
+            (Some(copy1), Some(copy2)) => {
+              let mut sub_game = Game::new(
+                self.params,
+                self.rng.fork(),
+                vec![PlayerDeck::new(copy1), PlayerDeck::new(copy2)],
+              );
+
+              let result = sub_game.play().0;
+              record.recursive_result = Some(result);
+
+              return match result {
+                GameResult::Winner(0) => {
+                  record.winner = Some(player1);
+                  (RoundResult::RoundWin(player1), record)
+                }
+                GameResult::Winner(_) => {
+                  record.winner = Some(player2);
+                  (RoundResult::RoundWin(player2), record)
+                }
+                GameResult::Draw => (RoundResult::GameResult(GameResult::Draw), record),
+              };
+            }
+            // Not enough cards on one side to copy: fall back to the ordinary FaceDown
+            // war instead of recursing.
+            _ => record.face_down.push(self.flip_face_down(&contenders, fallback_k)),
+          }
+        }
+        WarMode::Recursive { fallback_k } => {
+          record.face_down.push(self.flip_face_down(&contenders, fallback_k))
         }
       }
     }
   }
 
   /// Plays this game to completion, returning the winner and the number of turns taken.
+  ///
+  /// Terminates with a `Draw` if `Params::max_turns` is reached, or — only when
+  /// `Params::shuffle_discards` is `false` — if the exact same game state (every
+  /// player's deck and discard contents, in order) recurs. With `shuffle_discards`
+  /// enabled, won piles are reshuffled by the RNG before being redrawn, so the deck and
+  /// discard contents alone don't capture enough state to detect a true cycle: an
+  /// identical layout could still be on a different, non-repeating RNG trajectory, and
+  /// hashing it on every turn would just be wasted work.
   pub fn play(&mut self) -> (GameResult, u64) {
     let mut turn = 0;
+    let mut seen_states = HashSet::new();
+
     loop {
+      if self.params.max_turns.is_some_and(|max_turns| turn >= max_turns) {
+        return (GameResult::Draw, turn);
+      }
       turn += 1;
 
-      match self.play_round() {
-        RoundResult::RoundWin(Player::Player1) => self.player1.win_loot(&self.work),
-        RoundResult::RoundWin(Player::Player2) => self.player2.win_loot(&self.work),
+      if !self.params.shuffle_discards {
+        let mut hasher = DefaultHasher::new();
+        self.players.hash(&mut hasher);
+        if !seen_states.insert(hasher.finish()) {
+          return (GameResult::Draw, turn);
+        }
+      }
+
+      let (round_result, record) = self.play_round();
+      if let Some(history) = &mut self.history {
+        history.push(record);
+      }
+
+      match round_result {
+        RoundResult::RoundWin(winner) => self.players[winner].win_loot(&self.work),
         RoundResult::GameResult(result) => return (result, turn),
       }
     }
@@ -0,0 +1,170 @@
+use crate::sim::{Card, Game, GameResult, Params, PlayerDeck};
+use fastrand::Rng;
+use std::collections::BTreeMap;
+use std::thread;
+
+/// Aggregated outcome and game-length statistics from simulating many games dealt from
+/// the same deck under the same `Params`.
+pub struct BatchStats {
+  /// Wins per player, indexed the same way as the `Params::players` they were dealt.
+  wins: Vec<u64>,
+  draws: u64,
+  /// One entry per game played, for the turn-count statistics below.
+  turns: Vec<u64>,
+}
+
+impl BatchStats {
+  fn new(players: usize) -> Self {
+    Self {
+      wins: vec![0; players],
+      draws: 0,
+      turns: Vec::new(),
+    }
+  }
+
+  fn record(&mut self, result: GameResult, turns: u64) {
+    match result {
+      GameResult::Winner(player) => self.wins[player] += 1,
+      GameResult::Draw => self.draws += 1,
+    }
+    self.turns.push(turns);
+  }
+
+  fn merge(&mut self, other: BatchStats) {
+    for (wins, other_wins) in self.wins.iter_mut().zip(other.wins) {
+      *wins += other_wins;
+    }
+    self.draws += other.draws;
+    self.turns.extend(other.turns);
+  }
+
+  /// The number of games played.
+  pub fn games(&self) -> u64 {
+    self.turns.len() as u64
+  }
+
+  /// The fraction of games `player` won, with a 95% confidence interval half-width.
+  pub fn win_rate(&self, player: usize) -> (f64, f64) {
+    confidence_interval(self.wins[player], self.games())
+  }
+
+  /// The fraction of games that drew, with a 95% confidence interval half-width.
+  pub fn draw_rate(&self) -> (f64, f64) {
+    confidence_interval(self.draws, self.games())
+  }
+
+  /// The mean number of turns across all games played, or `0.0` if no games were
+  /// played.
+  pub fn mean_turns(&self) -> f64 {
+    if self.turns.is_empty() {
+      return 0.0;
+    }
+    self.turns.iter().sum::<u64>() as f64 / self.games() as f64
+  }
+
+  /// The median number of turns across all games played, or `0` if no games were
+  /// played.
+  pub fn median_turns(&self) -> u64 {
+    if self.turns.is_empty() {
+      return 0;
+    }
+    let mut turns = self.turns.clone();
+    turns.sort_unstable();
+    turns[turns.len() / 2]
+  }
+
+  /// The longest game played, in turns.
+  pub fn max_turns(&self) -> u64 {
+    self.turns.iter().copied().max().unwrap_or(0)
+  }
+
+  /// A histogram of game lengths: the number of games falling into each `bucket_size`-
+  /// turn-wide bucket, keyed by the bucket's lower bound, in ascending order.
+  pub fn turns_histogram(&self, bucket_size: u64) -> Vec<(u64, u64)> {
+    let mut buckets = BTreeMap::new();
+    for &turns in &self.turns {
+      *buckets.entry(turns / bucket_size * bucket_size).or_insert(0) += 1;
+    }
+    buckets.into_iter().collect()
+  }
+}
+
+/// A 95% confidence interval (Wald approximation) for a binomial rate of `successes`
+/// out of `n` trials. Returns `(rate, half_width)`, or `(0.0, 0.0)` if `n` is zero.
+fn confidence_interval(successes: u64, n: u64) -> (f64, f64) {
+  if n == 0 {
+    return (0.0, 0.0);
+  }
+  let n = n as f64;
+  let rate = successes as f64 / n;
+  let half_width = 1.96 * (rate * (1.0 - rate) / n).sqrt();
+  (rate, half_width)
+}
+
+/// Splits `deck` into `players` roughly equal, contiguous piles.
+fn deal(deck: &[Card], players: usize) -> Vec<PlayerDeck> {
+  let base = deck.len() / players;
+  let remainder = deck.len() % players;
+
+  let mut piles = Vec::with_capacity(players);
+  let mut start = 0;
+  for i in 0..players {
+    let size = base + usize::from(i < remainder);
+    piles.push(PlayerDeck::new(deck[start..start + size].to_vec()));
+    start += size;
+  }
+  piles
+}
+
+/// Deals `n` random starting splits of `deck` among `params.players()` players, plays
+/// each to completion, and aggregates the results: per-player win frequencies and a
+/// draw frequency (with confidence intervals), plus mean/median/max/histogram
+/// game-length statistics.
+///
+/// The games are spread across `std::thread::available_parallelism` threads. Each
+/// game's `fastrand::Rng` is deterministically forked from a single `Rng` seeded with
+/// `seed`, in order of the game's global index among the `n` played — not in order of
+/// however work happens to be partitioned across threads — so a given
+/// `(params, deck, seed, n)` always produces the same result regardless of how many
+/// threads are available.
+pub fn simulate_batch(params: Params, deck: Vec<Card>, seed: u64, n: u64) -> BatchStats {
+  let thread_count = thread::available_parallelism()
+    .map_or(1, |count| count.get() as u64)
+    .min(n.max(1));
+
+  let mut base_rng = Rng::with_seed(seed);
+  let game_rngs: Vec<Rng> = (0..n).map(|_| base_rng.fork()).collect();
+
+  thread::scope(|scope| {
+    let mut remaining = &game_rngs[..];
+    let handles: Vec<_> = (0..thread_count)
+      .map(|i| {
+        let games = (n / thread_count + u64::from(i < n % thread_count)) as usize;
+        let (chunk, rest) = remaining.split_at(games);
+        remaining = rest;
+        let deck = &deck;
+
+        scope.spawn(move || {
+          let mut stats = BatchStats::new(params.players());
+          for rng in chunk {
+            let mut rng = rng.clone();
+            let mut deck = deck.clone();
+            rng.shuffle(&mut deck);
+            let players = deal(&deck, params.players());
+
+            let mut game = Game::new(params, rng.fork(), players);
+            let (result, turns) = game.play();
+            stats.record(result, turns);
+          }
+          stats
+        })
+      })
+      .collect();
+
+    let mut stats = BatchStats::new(params.players());
+    for handle in handles {
+      stats.merge(handle.join().unwrap());
+    }
+    stats
+  })
+}